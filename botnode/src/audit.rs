@@ -0,0 +1,59 @@
+//! Audit engine
+//!
+//! Records the book/position snapshots the trading engine publishes, so
+//! there's a trail of what the bot saw and did. For now this just logs
+//! every [`TradingData`] update; a persistent sink can replace that once
+//! one exists.
+
+use crate::backoff::Backoff;
+use crate::prelude::*;
+use crate::trading::TradingData;
+
+/// Audit engine
+pub struct AuditEngine {
+    trading_rx: spsc_queue::Consumer<TradingData>,
+}
+
+impl AuditEngine {
+    pub fn new(trading_rx: spsc_queue::Consumer<TradingData>) -> Self {
+        Self { trading_rx }
+    }
+}
+
+#[async_trait(?Send)]
+impl Engine for AuditEngine {
+    type Data = ();
+
+    async fn start(mut self, shutdown: Shutdown) -> Result<(), EngineError> {
+        info!("Starting audit engine");
+
+        let mut backoff = Backoff::new();
+
+        loop {
+            if shutdown.shutdown_started() {
+                break;
+            }
+
+            if let Some(data) = self.trading_rx.try_pop() {
+                info!("audit: {:?}", data);
+                backoff.reset();
+            } else {
+                backoff.idle().await;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns dummy data receiver
+    fn data_rx(&mut self) -> spsc_queue::Consumer<Self::Data> {
+        let (_data_tx, data_rx) = spsc_queue::make::<()>(1024);
+        data_rx
+    }
+}
+
+impl ToString for AuditEngine {
+    fn to_string(&self) -> String {
+        "audit-engine".to_string()
+    }
+}
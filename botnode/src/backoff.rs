@@ -0,0 +1,68 @@
+//! Adaptive idle backoff for busy-polling loops
+//!
+//! The trading loop (and `engine::await_value`) spin on `try_pop()`
+//! rather than blocking, since the spsc queues have no way to wake a
+//! waiter. Spinning unconditionally burns a full core even when idle, so
+//! [`Backoff`] ramps from a tight spin up through yielding the task to a
+//! capped async park, resetting to the tight spin the moment work shows
+//! up again.
+//!
+//! `idle` is an `async fn`: every branch, including the spin phase, goes
+//! through a real await point (`glommio::yield_if_needed`/`timer::sleep`)
+//! rather than blocking the underlying thread. A loop built on top of it
+//! is therefore an `.await`-bearing future that an `async_shutdown`
+//! cancellation race can actually interleave with, instead of one that
+//! runs to completion the instant it's polled.
+
+use std::time::Duration;
+
+use glommio::timer::sleep;
+use glommio::yield_if_needed;
+
+/// Consecutive idle polls spent purely spinning before we start yielding
+const SPIN_LIMIT: u32 = 100;
+/// Consecutive idle polls spent yielding before we start parking
+const YIELD_LIMIT: u32 = 200;
+/// Ceiling on how long a single park can last
+const MAX_PARK: Duration = Duration::from_micros(500);
+
+/// Tracks how long a loop has been idle and waits increasingly patiently
+/// between polls as a result
+#[derive(Default)]
+pub struct Backoff {
+    idle_polls: u32,
+}
+
+impl Backoff {
+    pub fn new() -> Self {
+        Self { idle_polls: 0 }
+    }
+
+    /// Call when a poll found no work: spins, yields, or parks depending
+    /// on how long the loop has been idle for. The spin and yield phases
+    /// await `yield_if_needed`, which hands control back to the executor
+    /// only once the task has used up its time slice; the park phase
+    /// always suspends for the park duration.
+    pub async fn idle(&mut self) {
+        if self.idle_polls < SPIN_LIMIT {
+            std::hint::spin_loop();
+            yield_if_needed().await;
+        } else if self.idle_polls < YIELD_LIMIT {
+            yield_if_needed().await;
+        } else {
+            let ramp = self.idle_polls - YIELD_LIMIT;
+            // 1 << 19 ~= 524_288ns, comfortably past MAX_PARK, so the
+            // ramp actually reaches the cap instead of plateauing short of it
+            let park = Duration::from_nanos(1 << ramp.min(19)).min(MAX_PARK);
+            sleep(park).await;
+        }
+
+        self.idle_polls = self.idle_polls.saturating_add(1);
+    }
+
+    /// Call when a poll found work: resets the ramp so the next idle
+    /// stretch starts back at a tight spin
+    pub fn reset(&mut self) {
+        self.idle_polls = 0;
+    }
+}
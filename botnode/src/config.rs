@@ -0,0 +1,90 @@
+//! Bot-local configuration
+//!
+//! Unlike the runtime configuration the control engine receives from
+//! botvana-server once connected, this is loaded once at startup so the
+//! bot knows its identity and which exchanges/symbols to trade before it
+//! ever talks to the server: first a `.env` file if present, then
+//! `BOT_ID`/`SERVER_ADDR`/`METRICS_ADDR` from the environment, then a
+//! markets manifest (`markets.json`, or the path in `CONFIG_FILE`).
+
+use std::env::var;
+use std::fs;
+use std::net::SocketAddr;
+
+use serde::Deserialize;
+use tracing::debug;
+
+use botvana::net::msg::BotId;
+
+use crate::engine::EngineError;
+
+/// One exchange to connect to: which symbols to subscribe and at which
+/// indicator resolutions
+#[derive(Clone, Debug, Deserialize)]
+pub struct ExchangeConfig {
+    pub exchange: String,
+    pub symbols: Vec<String>,
+    #[serde(default = "default_resolutions_secs")]
+    pub indicator_resolutions_secs: Vec<u64>,
+}
+
+fn default_resolutions_secs() -> Vec<u64> {
+    vec![1, 60, 300]
+}
+
+/// Markets manifest: the exchanges/symbols this bot should trade
+#[derive(Clone, Debug, Deserialize)]
+struct MarketsManifest {
+    exchanges: Vec<ExchangeConfig>,
+}
+
+/// Bot-local configuration, assembled from the environment and the
+/// markets manifest
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub bot_id: BotId,
+    pub server_addr: String,
+    pub metrics_addr: SocketAddr,
+    pub exchanges: Vec<ExchangeConfig>,
+}
+
+impl Config {
+    /// Loads a `.env` file if present, then the environment, then the
+    /// markets manifest, returning an [`EngineError`] instead of panicking
+    /// if anything is missing or malformed
+    pub fn load() -> Result<Self, EngineError> {
+        if let Err(e) = dotenv::dotenv() {
+            debug!("no .env file loaded: {}", e);
+        }
+
+        let bot_id = var("BOT_ID")
+            .map_err(|_| EngineError::Configuration("missing BOT_ID".to_string()))?
+            .parse::<BotId>()
+            .map_err(|_| EngineError::Configuration("BOT_ID must be a u16 number".to_string()))?;
+
+        let server_addr =
+            var("SERVER_ADDR").map_err(|_| EngineError::Configuration("missing SERVER_ADDR".to_string()))?;
+
+        let metrics_addr = var("METRICS_ADDR")
+            .unwrap_or_else(|_| "0.0.0.0:9090".to_string())
+            .parse::<SocketAddr>()
+            .map_err(|_| {
+                EngineError::Configuration("METRICS_ADDR must be a socket address".to_string())
+            })?;
+
+        let config_file = var("CONFIG_FILE").unwrap_or_else(|_| "markets.json".to_string());
+        let manifest = fs::read_to_string(&config_file).map_err(|e| {
+            EngineError::Configuration(format!("failed to read {}: {}", config_file, e))
+        })?;
+        let manifest: MarketsManifest = serde_json::from_str(&manifest).map_err(|e| {
+            EngineError::Configuration(format!("failed to parse {}: {}", config_file, e))
+        })?;
+
+        Ok(Config {
+            bot_id,
+            server_addr,
+            metrics_addr,
+            exchanges: manifest.exchanges,
+        })
+    }
+}
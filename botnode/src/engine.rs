@@ -0,0 +1,31 @@
+//! Engine helpers shared across subsystems
+//!
+//! Supplements the `Engine` trait with a config-wait helper that mirrors
+//! the trading loop's shutdown-aware adaptive backoff (see
+//! [`crate::backoff`]): waits for a value on an spsc queue without
+//! pinning a core, and bails out as soon as shutdown starts instead of
+//! spinning forever on a config that will never arrive.
+
+use async_shutdown::Shutdown;
+
+use crate::backoff::Backoff;
+
+/// Waits for a value on `rx`, backing off adaptively while idle.
+/// Returns `None` as soon as `shutdown` fires, so a caller blocked on
+/// configuration that never arrives doesn't stop the process from
+/// shutting down cleanly.
+pub async fn await_value<T>(rx: spsc_queue::Consumer<T>, shutdown: &Shutdown) -> Option<T> {
+    let mut backoff = Backoff::new();
+
+    loop {
+        if let Some(value) = rx.try_pop() {
+            return Some(value);
+        }
+
+        if shutdown.shutdown_started() {
+            return None;
+        }
+
+        backoff.idle().await;
+    }
+}
@@ -0,0 +1,155 @@
+//! Indicator engine
+
+use std::collections::HashMap;
+
+use crate::backoff::Backoff;
+use crate::metrics::INDICATOR_EVENTS;
+use crate::prelude::*;
+
+mod candle;
+
+pub use candle::{Candle, Resolution};
+
+use candle::CandleAggregator;
+
+/// Indicator events emitted downstream to [`crate::trading::run_trading_loop`]
+#[derive(Clone, Debug)]
+pub enum IndicatorEvent {
+    /// A candle has closed for `(exchange, symbol, resolution)`
+    CandleClosed {
+        exchange: String,
+        symbol: String,
+        resolution: Resolution,
+        candle: Candle,
+    },
+}
+
+/// Indicator engine
+///
+/// Consumes market data from every configured exchange and derives
+/// indicators from it, publishing [`IndicatorEvent`]s for the trading
+/// engine to act on. Which resolutions are aggregated per exchange comes
+/// from that exchange's `indicator_resolutions_secs` in the markets
+/// manifest, so adding or changing a resolution needs no recompile.
+pub struct IndicatorEngine {
+    config_rx: spsc_queue::Consumer<Config>,
+    market_data_rx: HashMap<String, Vec<spsc_queue::Consumer<MarketEvent>>>,
+    data_tx: spsc_queue::Producer<IndicatorEvent>,
+    data_rx: Option<spsc_queue::Consumer<IndicatorEvent>>,
+    aggregators: HashMap<(String, String, Resolution), CandleAggregator>,
+    resolutions: HashMap<String, Vec<Resolution>>,
+}
+
+impl IndicatorEngine {
+    pub fn new(
+        config_rx: spsc_queue::Consumer<Config>,
+        market_data_rx: HashMap<String, Vec<spsc_queue::Consumer<MarketEvent>>>,
+        resolutions: HashMap<String, Vec<Resolution>>,
+    ) -> Self {
+        let (data_tx, data_rx) = spsc_queue::make::<IndicatorEvent>(1024);
+
+        Self {
+            config_rx,
+            market_data_rx,
+            data_tx,
+            data_rx: Some(data_rx),
+            aggregators: HashMap::new(),
+            resolutions,
+        }
+    }
+
+    /// Returns the aggregator for `(exchange, symbol, resolution)`, creating
+    /// one seeded from `price` if this is the first trade seen for that key
+    fn aggregator(
+        &mut self,
+        exchange: &str,
+        symbol: &str,
+        resolution: Resolution,
+        timestamp: i64,
+        price: f64,
+    ) -> &mut CandleAggregator {
+        self.aggregators
+            .entry((exchange.to_string(), symbol.to_string(), resolution))
+            .or_insert_with(|| CandleAggregator::new(resolution, timestamp, price))
+    }
+
+    /// Feeds a single trade into every resolution configured for
+    /// `exchange`'s aggregator, publishing a [`IndicatorEvent::CandleClosed`]
+    /// for each one that completes a bucket
+    fn on_trade(&mut self, exchange: &str, symbol: &str, timestamp: i64, price: f64, size: f64) {
+        let resolutions = self.resolutions.get(exchange).cloned().unwrap_or_default();
+
+        for resolution in resolutions {
+            let aggregator = self.aggregator(exchange, symbol, resolution, timestamp, price);
+
+            for candle in aggregator.update(timestamp, price, size) {
+                INDICATOR_EVENTS.with_label_values(&["candle"]).inc();
+
+                self.data_tx.push(IndicatorEvent::CandleClosed {
+                    exchange: exchange.to_string(),
+                    symbol: symbol.to_string(),
+                    resolution,
+                    candle,
+                });
+            }
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl Engine for IndicatorEngine {
+    type Data = IndicatorEvent;
+
+    async fn start(mut self, shutdown: Shutdown) -> Result<(), EngineError> {
+        info!("Starting indicator engine");
+
+        let mut backoff = Backoff::new();
+
+        loop {
+            if shutdown.shutdown_started() {
+                break;
+            }
+
+            let mut trades = Vec::new();
+
+            for (exchange, rxs) in self.market_data_rx.iter() {
+                for rx in rxs {
+                    if let Some(MarketEvent::Trade {
+                        symbol,
+                        price,
+                        size,
+                        timestamp,
+                        ..
+                    }) = rx.try_pop()
+                    {
+                        trades.push((exchange.clone(), symbol, timestamp, price, size));
+                    }
+                }
+            }
+
+            let had_work = !trades.is_empty();
+
+            for (exchange, symbol, timestamp, price, size) in trades {
+                self.on_trade(&exchange, &symbol, timestamp, price, size);
+            }
+
+            if had_work {
+                backoff.reset();
+            } else {
+                backoff.idle().await;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn data_rx(&mut self) -> spsc_queue::Consumer<Self::Data> {
+        self.data_rx.take().expect("indicator data_rx already taken")
+    }
+}
+
+impl ToString for IndicatorEngine {
+    fn to_string(&self) -> String {
+        "indicator-engine".to_string()
+    }
+}
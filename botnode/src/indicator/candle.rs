@@ -0,0 +1,173 @@
+//! OHLCV candle aggregation
+
+/// A fixed-size time bucket a [`CandleAggregator`] rolls up trades into
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Candle {
+    pub bucket_start: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+/// A candle resolution: a bucket length in seconds or in minutes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Resolution {
+    Seconds(i64),
+    Minutes(i64),
+}
+
+impl Resolution {
+    /// Length of the bucket this resolution represents, in seconds
+    fn as_secs(&self) -> i64 {
+        match self {
+            Resolution::Seconds(n) => *n,
+            Resolution::Minutes(n) => n * 60,
+        }
+    }
+
+    /// Start of the bucket that `timestamp` (unix seconds) falls into
+    fn bucket_start(&self, timestamp: i64) -> i64 {
+        let secs = self.as_secs();
+
+        timestamp - (timestamp % secs)
+    }
+}
+
+/// Rolls trades for a single `(exchange, symbol, resolution)` key into
+/// [`Candle`]s, emitting the previous candle once a trade lands in a new
+/// bucket
+pub struct CandleAggregator {
+    resolution: Resolution,
+    current: Candle,
+}
+
+impl CandleAggregator {
+    /// Creates an aggregator with its first bucket opened at `price`
+    pub fn new(resolution: Resolution, timestamp: i64, price: f64) -> Self {
+        let bucket_start = resolution.bucket_start(timestamp);
+
+        Self {
+            resolution,
+            current: Candle {
+                bucket_start,
+                open: price,
+                high: price,
+                low: price,
+                close: price,
+                volume: 0.0,
+            },
+        }
+    }
+
+    /// Folds a trade into the aggregator, returning every candle that
+    /// closed as a result: just the previous bucket if `timestamp` moved
+    /// into the very next one, or that bucket plus one zero-volume
+    /// filler per bucket skipped over if trades stopped arriving for a
+    /// while, each carrying the last trade's close forward until the new
+    /// trade's own bucket opens fresh at its price.
+    pub fn update(&mut self, timestamp: i64, price: f64, size: f64) -> Vec<Candle> {
+        let bucket_start = self.resolution.bucket_start(timestamp);
+
+        if bucket_start == self.current.bucket_start {
+            self.current.high = self.current.high.max(price);
+            self.current.low = self.current.low.min(price);
+            self.current.close = price;
+            self.current.volume += size;
+
+            return Vec::new();
+        }
+
+        let mut closed = vec![self.current];
+        let bucket_len = self.resolution.as_secs();
+        let mut next_start = self.current.bucket_start + bucket_len;
+
+        while next_start < bucket_start {
+            let close = self.current.close;
+            let filler = Candle {
+                bucket_start: next_start,
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: 0.0,
+            };
+
+            self.current = filler;
+            closed.push(filler);
+            next_start += bucket_len;
+        }
+
+        self.current = Candle {
+            bucket_start,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: size,
+        };
+
+        closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_within_the_same_bucket_rolls_high_low_close_and_volume() {
+        let mut aggregator = CandleAggregator::new(Resolution::Seconds(1), 100, 10.0);
+
+        assert!(aggregator.update(100, 12.0, 1.0).is_empty());
+        assert!(aggregator.update(100, 8.0, 1.0).is_empty());
+
+        assert_eq!(aggregator.current.open, 10.0);
+        assert_eq!(aggregator.current.high, 12.0);
+        assert_eq!(aggregator.current.low, 8.0);
+        assert_eq!(aggregator.current.close, 8.0);
+        assert_eq!(aggregator.current.volume, 2.0);
+    }
+
+    #[test]
+    fn update_into_the_next_bucket_closes_exactly_one_candle() {
+        let mut aggregator = CandleAggregator::new(Resolution::Seconds(1), 100, 10.0);
+
+        let closed = aggregator.update(101, 11.0, 1.0);
+
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].bucket_start, 100);
+        assert_eq!(closed[0].close, 10.0);
+        assert_eq!(aggregator.current.bucket_start, 101);
+        assert_eq!(aggregator.current.open, 11.0);
+    }
+
+    #[test]
+    fn update_across_a_gap_backfills_zero_volume_fillers() {
+        let mut aggregator = CandleAggregator::new(Resolution::Seconds(1), 100, 10.0);
+
+        // Next trade lands 5 buckets later: bucket 100 closes for real,
+        // buckets 101-104 never saw a trade and should be backfilled at
+        // zero volume with the close carried forward, then bucket 105
+        // opens fresh at the new trade's price.
+        let closed = aggregator.update(105, 20.0, 2.0);
+
+        assert_eq!(closed.len(), 5);
+        assert_eq!(closed[0].bucket_start, 100);
+        assert_eq!(closed[0].close, 10.0);
+
+        for (i, candle) in closed[1..].iter().enumerate() {
+            assert_eq!(candle.bucket_start, 101 + i as i64);
+            assert_eq!(candle.open, 10.0);
+            assert_eq!(candle.high, 10.0);
+            assert_eq!(candle.low, 10.0);
+            assert_eq!(candle.close, 10.0);
+            assert_eq!(candle.volume, 0.0);
+        }
+
+        assert_eq!(aggregator.current.bucket_start, 105);
+        assert_eq!(aggregator.current.open, 20.0);
+        assert_eq!(aggregator.current.volume, 2.0);
+    }
+}
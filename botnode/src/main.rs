@@ -1,5 +1,4 @@
 use std::collections::HashMap;
-use std::env::var;
 use std::panic;
 
 use async_shutdown::Shutdown;
@@ -11,8 +10,10 @@ use tracing::{debug, error, info};
 use tracing_subscriber::prelude::*;
 use tracing_subscriber::{fmt, EnvFilter};
 
-use botnode::{audit::*, control::*, engine::*, indicator::*, market_data::*, trading::*};
-use botvana::net::msg::BotId;
+use botnode::{
+    audit::*, config::Config, control::*, engine::*, indicator::*, market_data::*, metrics::*,
+    trading::*,
+};
 
 #[global_allocator]
 static ALLOC: snmalloc_rs::SnMalloc = snmalloc_rs::SnMalloc;
@@ -23,7 +24,9 @@ fn main() {
         .with(fmt::layer().with_thread_names(true))
         .init();
 
-    let (bot_id, server_addr) = load_configuration();
+    let config = Config::load().expect("failed to load configuration");
+
+    info!("bot_id = {}", config.bot_id.0);
 
     let shutdown = Shutdown::new();
 
@@ -37,37 +40,35 @@ fn main() {
         }));
     }
 
-    // Stage 1: Start the control engine that will connect to botvana-server and
-    // receive the configuration.
+    // Stage 1: Start the control engine that will connect to botvana-server.
 
-    let mut control_engine = ControlEngine::new(bot_id, server_addr);
-    let mut config_rxs: Vec<_> = (1..5)
-        .into_iter()
+    let mut control_engine = ControlEngine::new(config.bot_id, config.server_addr);
+    // One config_rx per exchange market data engine, plus one for the indicator engine.
+    let mut config_rxs: Vec<_> = (0..config.exchanges.len() + 1)
         .map(|_| control_engine.data_rx())
         .collect();
 
     start_engine(0, control_engine, shutdown.clone()).expect("failed to start control engine");
 
-    debug!("Waiting for configuration");
-    let config = await_value(config_rxs.pop().unwrap());
     let mut market_data_rxs = vec![HashMap::new(), HashMap::new()];
 
     for (i, exchange) in config.exchanges.iter().enumerate() {
         debug!("starting exchange {:?}", exchange);
 
-        match exchange.as_ref() {
+        match exchange.exchange.as_ref() {
             "ftx" => {
-                let ftx_adapter = botnode::market_data::ftx::Ftx {
-                    metrics: botnode::market_data::ftx::FtxMetrics::default(),
-                };
+                // Per-exchange counters now live on the shared REGISTRY
+                // (see botnode::metrics) instead of an ad-hoc FtxMetrics
+                // struct, so the adapter no longer needs one wired in.
+                let ftx_adapter = botnode::market_data::ftx::Ftx::default();
                 let mut market_data_engine =
                     MarketDataEngine::new(config_rxs.pop().unwrap(), ftx_adapter);
                 market_data_rxs[0].insert(
-                    exchange.clone(),
+                    exchange.exchange.clone(),
                     vec![market_data_engine.data_rx(), market_data_engine.data_rx()],
                 );
                 market_data_rxs[1].insert(
-                    exchange.clone(),
+                    exchange.exchange.clone(),
                     vec![market_data_engine.data_rx(), market_data_engine.data_rx()],
                 );
 
@@ -79,11 +80,11 @@ fn main() {
                 let mut market_data_engine =
                     MarketDataEngine::new(config_rxs.pop().unwrap(), binance_adapter);
                 market_data_rxs[0].insert(
-                    exchange.clone(),
+                    exchange.exchange.clone(),
                     vec![market_data_engine.data_rx(), market_data_engine.data_rx()],
                 );
                 market_data_rxs[1].insert(
-                    exchange.clone(),
+                    exchange.exchange.clone(),
                     vec![market_data_engine.data_rx(), market_data_engine.data_rx()],
                 );
 
@@ -91,20 +92,37 @@ fn main() {
                     .expect("failed to start market data engine");
             }
             _ => {
-                error!("Unknown exchange {}", exchange);
+                error!("Unknown exchange {}", exchange.exchange);
             }
         }
     }
 
-    let mut indicator_engine =
-        IndicatorEngine::new(config_rxs.pop().unwrap(), market_data_rxs.pop().unwrap());
+    let indicator_resolutions: HashMap<String, Vec<Resolution>> = config
+        .exchanges
+        .iter()
+        .map(|exchange| {
+            let resolutions = exchange
+                .indicator_resolutions_secs
+                .iter()
+                .map(|secs| Resolution::Seconds(*secs as i64))
+                .collect();
+
+            (exchange.exchange.clone(), resolutions)
+        })
+        .collect();
+
+    let mut indicator_engine = IndicatorEngine::new(
+        config_rxs.pop().unwrap(),
+        market_data_rxs.pop().unwrap(),
+        indicator_resolutions,
+    );
 
-    let trading_engine =
+    let mut trading_engine =
         TradingEngine::new(market_data_rxs.pop().unwrap(), indicator_engine.data_rx());
 
     start_engine(
         config.exchanges.len() + 2,
-        AuditEngine::new(),
+        AuditEngine::new(trading_engine.data_rx()),
         shutdown.clone(),
     )
     .expect("failed to start audit engine");
@@ -119,29 +137,19 @@ fn main() {
     )
     .expect("failed to start indicator engine");
 
+    start_engine(
+        config.exchanges.len() + 5,
+        MetricsEngine::new(config.metrics_addr),
+        shutdown.clone(),
+    )
+    .expect("failed to start metrics engine");
+
     // Setup signal handlers for shutdown
     let signals = Signals::new(&[SIGINT, SIGTERM, SIGQUIT]).expect("Failed to register signals");
     let local_ex = LocalExecutor::default();
     local_ex.run(handle_signals(signals, shutdown));
 }
 
-/// Loads configuration from ENV variables
-///
-/// Panics if the BOT_ID or SERVER_ADDR variables are missing or
-/// BOT_ID can't be parsed as u16 number.
-fn load_configuration() -> (BotId, String) {
-    let bot_id = var("BOT_ID")
-        .expect("Please specify BOT_ID")
-        .parse::<BotId>()
-        .expect("BOT_ID must be u16 number");
-
-    info!("bot_id = {}", bot_id.0);
-
-    let server_addr = var("SERVER_ADDR").expect("Please specify SERVER_ADDR");
-
-    (bot_id, server_addr)
-}
-
 /// Handles shutdown signals from OS
 ///
 /// The function will wait for one of SIGTERM, SIGINT or SIGQUIT signals
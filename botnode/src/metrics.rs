@@ -0,0 +1,130 @@
+//! Prometheus metrics engine
+//!
+//! Owns the process-wide [`prometheus::Registry`] and serves it over a
+//! small HTTP server so a Prometheus server can scrape `/metrics`. Other
+//! engines push into the shared metric handles defined here instead of
+//! keeping their own ad-hoc counters.
+
+use std::net::SocketAddr;
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server};
+use once_cell::sync::Lazy;
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, IntCounter, IntCounterVec, Opts, Registry, TextEncoder,
+};
+
+use crate::prelude::*;
+
+/// Process-wide registry that every engine's metrics are registered against
+pub static REGISTRY: Lazy<Registry> = Lazy::new(Registry::new);
+
+/// Market data messages processed, labeled by exchange
+pub static MARKET_DATA_MESSAGES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec(
+        "botnode_market_data_messages_total",
+        "Number of market data messages processed, by exchange",
+        &["exchange"],
+    )
+});
+
+/// Indicator events emitted, labeled by indicator kind
+pub static INDICATOR_EVENTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec(
+        "botnode_indicator_events_total",
+        "Number of indicator events emitted, by kind",
+        &["kind"],
+    )
+});
+
+/// Trading loop iterations
+pub static TRADING_LOOP_ITERATIONS: Lazy<IntCounter> = Lazy::new(|| {
+    let counter = IntCounter::new(
+        "botnode_trading_loop_iterations_total",
+        "Number of iterations of the trading engine loop",
+    )
+    .expect("failed to create botnode_trading_loop_iterations_total counter");
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .expect("failed to register botnode_trading_loop_iterations_total counter");
+    counter
+});
+
+/// Trading loop iteration latency, in seconds
+pub static TRADING_LOOP_LATENCY: Lazy<Histogram> = Lazy::new(|| {
+    let histogram = Histogram::with_opts(HistogramOpts::new(
+        "botnode_trading_loop_latency_seconds",
+        "Latency of a single trading engine loop iteration",
+    ))
+    .expect("failed to create botnode_trading_loop_latency_seconds histogram");
+    REGISTRY
+        .register(Box::new(histogram.clone()))
+        .expect("failed to register botnode_trading_loop_latency_seconds histogram");
+    histogram
+});
+
+fn register_int_counter_vec(name: &str, help: &str, labels: &[&str]) -> IntCounterVec {
+    let counter = IntCounterVec::new(Opts::new(name, help), labels)
+        .unwrap_or_else(|_| panic!("failed to create {} counter", name));
+    REGISTRY
+        .register(Box::new(counter.clone()))
+        .unwrap_or_else(|_| panic!("failed to register {} counter", name));
+    counter
+}
+
+/// Metrics engine
+///
+/// Serves the process-wide [`REGISTRY`] over HTTP on `bind_addr` so that a
+/// Prometheus scraper can pull `/metrics`.
+pub struct MetricsEngine {
+    bind_addr: SocketAddr,
+}
+
+impl MetricsEngine {
+    pub fn new(bind_addr: SocketAddr) -> Self {
+        Self { bind_addr }
+    }
+}
+
+#[async_trait(?Send)]
+impl Engine for MetricsEngine {
+    type Data = ();
+
+    async fn start(mut self, shutdown: Shutdown) -> Result<(), EngineError> {
+        info!("Starting metrics engine, listening on {}", self.bind_addr);
+
+        let make_svc =
+            make_service_fn(|_conn| async { Ok::<_, hyper::Error>(service_fn(serve_metrics)) });
+
+        let server = Server::bind(&self.bind_addr).serve(make_svc);
+
+        if let Some(Err(e)) = shutdown.wrap_cancel(server).await {
+            error!("metrics server error: {}", e);
+        }
+
+        Ok(())
+    }
+
+    /// Returns dummy data receiver
+    fn data_rx(&mut self) -> spsc_queue::Consumer<Self::Data> {
+        let (_data_tx, data_rx) = spsc_queue::make::<()>(1024);
+        data_rx
+    }
+}
+
+impl ToString for MetricsEngine {
+    fn to_string(&self) -> String {
+        "metrics-engine".to_string()
+    }
+}
+
+/// Renders the process registry in the Prometheus text exposition format
+async fn serve_metrics(_req: Request<Body>) -> Result<Response<Body>, hyper::Error> {
+    let metric_families = REGISTRY.gather();
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&metric_families, &mut buffer)
+        .expect("failed to encode metrics");
+
+    Ok(Response::new(Body::from(buffer)))
+}
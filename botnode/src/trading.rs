@@ -1,11 +1,105 @@
 //! Trading engine
 
+use std::collections::HashMap;
+use std::time::Instant;
+
+use chrono::{NaiveTime, Utc, Weekday};
+
+use crate::backoff::Backoff;
+use crate::metrics::{MARKET_DATA_MESSAGES, TRADING_LOOP_ITERATIONS, TRADING_LOOP_LATENCY};
 use crate::prelude::*;
 
+mod orderbook;
+mod schedule;
+
+pub use orderbook::{Fill, Order, OrderBook, OrderKind, Side};
+pub use schedule::{Recurrence, Schedule, ScheduledAction};
+
+/// Order submitted to, or cancelled from, the trading engine's books
+#[derive(Clone, Debug)]
+pub enum OrderEvent {
+    Submit(Order),
+    Cancel {
+        symbol: String,
+        side: Side,
+        price: f64,
+        order_id: u64,
+    },
+}
+
+/// Net position and PnL for a single symbol
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Position {
+    pub net_size: f64,
+    pub avg_entry_price: f64,
+    pub realized_pnl: f64,
+    pub unrealized_pnl: f64,
+}
+
+impl Position {
+    /// Folds a fill into the position, realizing PnL on any portion that
+    /// closes existing exposure and rolling the average entry price on any
+    /// portion that adds to it
+    fn apply_fill(&mut self, fill: &Fill) {
+        let signed_size = match fill.side {
+            Side::Buy => fill.size,
+            Side::Sell => -fill.size,
+        };
+
+        let same_direction = self.net_size == 0.0 || self.net_size.signum() == signed_size.signum();
+
+        if same_direction {
+            let new_size = self.net_size + signed_size;
+            self.avg_entry_price = (self.avg_entry_price * self.net_size.abs()
+                + fill.price * signed_size.abs())
+                / new_size.abs();
+            self.net_size = new_size;
+        } else {
+            let closing_size = signed_size.abs().min(self.net_size.abs());
+            let pnl_per_unit = if self.net_size > 0.0 {
+                fill.price - self.avg_entry_price
+            } else {
+                self.avg_entry_price - fill.price
+            };
+
+            self.realized_pnl += pnl_per_unit * closing_size;
+            self.net_size += signed_size;
+
+            if self.net_size != 0.0 && self.net_size.signum() == signed_size.signum() {
+                // Flipped through flat into the opposite side
+                self.avg_entry_price = fill.price;
+            }
+        }
+    }
+
+    /// Marks unrealized PnL to `last_price`
+    fn mark_to_market(&mut self, last_price: f64) {
+        self.unrealized_pnl = if self.net_size >= 0.0 {
+            (last_price - self.avg_entry_price) * self.net_size
+        } else {
+            (self.avg_entry_price - last_price) * self.net_size.abs()
+        };
+    }
+}
+
+/// Book and position snapshot published for the audit engine to record
+#[derive(Clone, Debug, Default)]
+pub struct TradingData {
+    pub symbol: String,
+    pub best_bid: Option<f64>,
+    pub best_ask: Option<f64>,
+    pub position: Position,
+}
+
 /// Trading engine
 pub struct TradingEngine {
     market_data_rx: spsc_queue::Consumer<MarketEvent>,
     indicator_rx: spsc_queue::Consumer<IndicatorEvent>,
+    order_rx: spsc_queue::Consumer<OrderEvent>,
+    order_tx: Option<spsc_queue::Producer<OrderEvent>>,
+    data_tx: spsc_queue::Producer<TradingData>,
+    data_rx: Option<spsc_queue::Consumer<TradingData>>,
+    schedule: Schedule,
 }
 
 impl TradingEngine {
@@ -13,27 +107,56 @@ impl TradingEngine {
         market_data_rx: spsc_queue::Consumer<MarketEvent>,
         indicator_rx: spsc_queue::Consumer<IndicatorEvent>,
     ) -> Self {
+        let (order_tx, order_rx) = spsc_queue::make::<OrderEvent>(1024);
+        let (data_tx, data_rx) = spsc_queue::make::<TradingData>(1024);
+
+        let mut schedule = Schedule::new();
+        schedule.add(
+            Recurrence::Weekly {
+                weekday: Weekday::Fri,
+                time: NaiveTime::from_hms_opt(21, 0, 0).expect("valid time"),
+            },
+            ScheduledAction::FlattenPositions,
+            Utc::now(),
+        );
+
         Self {
             market_data_rx,
             indicator_rx,
+            order_rx,
+            order_tx: Some(order_tx),
+            data_tx,
+            data_rx: Some(data_rx),
+            schedule,
         }
     }
+
+    /// Returns the producer a strategy submits [`OrderEvent`]s through
+    pub fn order_tx(&mut self) -> spsc_queue::Producer<OrderEvent> {
+        self.order_tx.take().expect("order_tx already taken")
+    }
 }
 
 #[async_trait(?Send)]
 impl Engine for TradingEngine {
-    type Data = ();
+    type Data = TradingData;
 
     async fn start(mut self, shutdown: Shutdown) -> Result<(), EngineError> {
         info!("Starting trading engine");
 
-        run_trading_loop(self.market_data_rx, self.indicator_rx, shutdown).await
+        run_trading_loop(
+            self.market_data_rx,
+            self.indicator_rx,
+            self.order_rx,
+            self.data_tx,
+            self.schedule,
+            shutdown,
+        )
+        .await
     }
 
-    /// Returns dummy data receiver
     fn data_rx(&mut self) -> spsc_queue::Consumer<Self::Data> {
-        let (_data_tx, data_rx) = spsc_queue::make::<()>(1024);
-        data_rx
+        self.data_rx.take().expect("trading data_rx already taken")
     }
 }
 
@@ -44,18 +167,231 @@ impl ToString for TradingEngine {
 }
 
 /// Trading engine loop
+///
+/// Maintains a per-symbol [`OrderBook`] and [`Position`], matching strategy
+/// orders and incoming trade prints against it to paper-trade against live
+/// market data. Registers with `shutdown` so the engine stops promptly
+/// instead of spinning forever, and backs off adaptively while idle so it
+/// doesn't peg a core waiting on empty queues.
 pub async fn run_trading_loop(
     market_data_rx: spsc_queue::Consumer<MarketEvent>,
     indicator_rx: spsc_queue::Consumer<IndicatorEvent>,
-    _shutdown: Shutdown,
+    order_rx: spsc_queue::Consumer<OrderEvent>,
+    data_tx: spsc_queue::Producer<TradingData>,
+    schedule: Schedule,
+    shutdown: Shutdown,
 ) -> Result<(), EngineError> {
+    shutdown
+        .wrap_cancel(trading_loop(
+            market_data_rx,
+            indicator_rx,
+            order_rx,
+            data_tx,
+            schedule,
+            shutdown.clone(),
+        ))
+        .await;
+
+    info!("Trading loop stopped");
+
+    Ok(())
+}
+
+async fn trading_loop(
+    market_data_rx: spsc_queue::Consumer<MarketEvent>,
+    indicator_rx: spsc_queue::Consumer<IndicatorEvent>,
+    order_rx: spsc_queue::Consumer<OrderEvent>,
+    data_tx: spsc_queue::Producer<TradingData>,
+    mut schedule: Schedule,
+    shutdown: Shutdown,
+) {
+    let mut books: HashMap<String, OrderBook> = HashMap::new();
+    let mut positions: HashMap<String, Position> = HashMap::new();
+    let mut backoff = Backoff::new();
+
     loop {
-        if let Some(_event) = market_data_rx.try_pop() {
-            //info!("market data = {:?}", event);
+        // Belt-and-suspenders alongside `wrap_cancel`: even a loop that's
+        // never idle (and so never hits the `backoff.idle().await` yield
+        // point below) still checks in on shutdown every iteration.
+        if shutdown.shutdown_started() {
+            break;
+        }
+
+        let iteration_start = Instant::now();
+        let mut had_work = false;
+
+        for action in schedule.poll(Utc::now()) {
+            had_work = true;
+
+            match action {
+                ScheduledAction::FlattenPositions => {
+                    info!("schedule: flattening all positions");
+
+                    for position in positions.values_mut() {
+                        position.realized_pnl += position.unrealized_pnl;
+                        position.net_size = 0.0;
+                        position.avg_entry_price = 0.0;
+                        position.unrealized_pnl = 0.0;
+                    }
+                }
+                ScheduledAction::RollPositions => {
+                    info!("schedule: rolling positions to the next period");
+                }
+            }
+        }
+
+        if let Some(MarketEvent::Trade {
+            exchange,
+            symbol,
+            price,
+            size,
+            ..
+        }) = market_data_rx.try_pop()
+        {
+            had_work = true;
+            MARKET_DATA_MESSAGES.with_label_values(&[&exchange]).inc();
+
+            let book = books.entry(symbol.clone()).or_insert_with(OrderBook::new);
+            let fills = book.cross_trade(price, size);
+
+            if !fills.is_empty() {
+                let position = positions.entry(symbol.clone()).or_default();
+
+                for fill in &fills {
+                    position.apply_fill(fill);
+                }
+                position.mark_to_market(price);
+
+                data_tx.push(TradingData {
+                    symbol,
+                    best_bid: book.best_bid(),
+                    best_ask: book.best_ask(),
+                    position: *position,
+                });
+            }
         }
 
         if let Some(event) = indicator_rx.try_pop() {
+            had_work = true;
+
             info!("indicator = {:?}", event);
         }
+
+        if let Some(event) = order_rx.try_pop() {
+            had_work = true;
+
+            match event {
+                OrderEvent::Submit(order) => {
+                    let symbol = order.symbol.clone();
+                    let book = books.entry(symbol.clone()).or_insert_with(OrderBook::new);
+                    let fills = book.submit(order);
+
+                    if !fills.is_empty() {
+                        let last_price = fills[fills.len() - 1].price;
+                        let position = positions.entry(symbol.clone()).or_default();
+
+                        for fill in &fills {
+                            position.apply_fill(fill);
+                        }
+                        position.mark_to_market(last_price);
+
+                        data_tx.push(TradingData {
+                            symbol,
+                            best_bid: book.best_bid(),
+                            best_ask: book.best_ask(),
+                            position: *position,
+                        });
+                    }
+                }
+                OrderEvent::Cancel {
+                    symbol,
+                    side,
+                    price,
+                    order_id,
+                } => {
+                    if let Some(book) = books.get_mut(&symbol) {
+                        book.cancel(side, price, order_id);
+                    }
+                }
+            }
+        }
+
+        TRADING_LOOP_ITERATIONS.inc();
+        TRADING_LOOP_LATENCY.observe(iteration_start.elapsed().as_secs_f64());
+
+        if had_work {
+            backoff.reset();
+        } else {
+            backoff.idle().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(side: Side, price: f64, size: f64) -> Fill {
+        Fill {
+            order_id: 1,
+            side,
+            price,
+            size,
+        }
+    }
+
+    #[test]
+    fn apply_fill_opens_and_rolls_average_entry_price() {
+        let mut position = Position::default();
+
+        position.apply_fill(&fill(Side::Buy, 100.0, 1.0));
+        position.apply_fill(&fill(Side::Buy, 102.0, 1.0));
+
+        assert_eq!(position.net_size, 2.0);
+        assert_eq!(position.avg_entry_price, 101.0);
+        assert_eq!(position.realized_pnl, 0.0);
+    }
+
+    #[test]
+    fn apply_fill_realizes_pnl_on_a_closing_fill() {
+        let mut position = Position::default();
+
+        position.apply_fill(&fill(Side::Buy, 100.0, 2.0));
+        position.apply_fill(&fill(Side::Sell, 110.0, 1.0));
+
+        assert_eq!(position.net_size, 1.0);
+        assert_eq!(position.avg_entry_price, 100.0);
+        assert_eq!(position.realized_pnl, 10.0);
+    }
+
+    #[test]
+    fn apply_fill_flips_through_flat_to_the_opposite_side() {
+        let mut position = Position::default();
+
+        position.apply_fill(&fill(Side::Buy, 100.0, 1.0));
+        position.apply_fill(&fill(Side::Sell, 110.0, 2.0));
+
+        assert_eq!(position.net_size, -1.0);
+        assert_eq!(position.avg_entry_price, 110.0);
+        assert_eq!(position.realized_pnl, 10.0);
+    }
+
+    #[test]
+    fn mark_to_market_tracks_unrealized_pnl_for_longs_and_shorts() {
+        let mut long = Position {
+            net_size: 1.0,
+            avg_entry_price: 100.0,
+            ..Default::default()
+        };
+        long.mark_to_market(110.0);
+        assert_eq!(long.unrealized_pnl, 10.0);
+
+        let mut short = Position {
+            net_size: -1.0,
+            avg_entry_price: 100.0,
+            ..Default::default()
+        };
+        short.mark_to_market(90.0);
+        assert_eq!(short.unrealized_pnl, 10.0);
     }
 }
@@ -0,0 +1,332 @@
+//! Per-symbol limit order book and simulated execution
+//!
+//! The book only holds orders we've submitted ourselves; it is crossed
+//! against incoming market liquidity (trade prints) rather than modelling
+//! the exchange's own book, which is enough to paper-trade a strategy.
+
+use std::collections::{BTreeMap, VecDeque};
+
+/// Fixed-point price, scaled so it can be used as a `BTreeMap` key without
+/// the ordering pitfalls of raw floats
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Price(i64);
+
+const PRICE_SCALE: f64 = 1e8;
+
+impl Price {
+    pub fn from_f64(price: f64) -> Self {
+        Self((price * PRICE_SCALE).round() as i64)
+    }
+
+    pub fn as_f64(self) -> f64 {
+        self.0 as f64 / PRICE_SCALE
+    }
+}
+
+/// Side of an order or fill
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+/// Order pricing style
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OrderKind {
+    Limit(f64),
+    Market,
+}
+
+/// An order submitted to an [`OrderBook`]
+#[derive(Clone, Debug)]
+pub struct Order {
+    pub id: u64,
+    pub symbol: String,
+    pub side: Side,
+    pub kind: OrderKind,
+    pub size: f64,
+}
+
+/// A resting price level; orders are matched in FIFO order within a level
+#[derive(Clone, Debug, Default)]
+struct Level {
+    orders: VecDeque<(u64, f64)>,
+}
+
+/// A fill produced either by a resting order being swept by incoming
+/// liquidity, or by a market order executing immediately
+#[derive(Clone, Copy, Debug)]
+pub struct Fill {
+    pub order_id: u64,
+    pub side: Side,
+    pub price: f64,
+    pub size: f64,
+}
+
+/// Per-symbol resting-order book, crossed against incoming market liquidity
+#[derive(Default)]
+pub struct OrderBook {
+    bids: BTreeMap<Price, Level>,
+    asks: BTreeMap<Price, Level>,
+    last_trade_price: Option<f64>,
+}
+
+impl OrderBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn best_bid(&self) -> Option<f64> {
+        self.bids.keys().next_back().map(|p| p.as_f64())
+    }
+
+    pub fn best_ask(&self) -> Option<f64> {
+        self.asks.keys().next().map(|p| p.as_f64())
+    }
+
+    /// Submits `order`. Limit orders rest on the book; market orders fill
+    /// immediately against the last observed trade price, if any.
+    pub fn submit(&mut self, order: Order) -> Vec<Fill> {
+        match order.kind {
+            OrderKind::Market => match self.last_trade_price {
+                Some(price) => vec![Fill {
+                    order_id: order.id,
+                    side: order.side,
+                    price,
+                    size: order.size,
+                }],
+                None => Vec::new(),
+            },
+            OrderKind::Limit(price) => {
+                self.insert(order.side, price, order.id, order.size);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Rests an order at `price` on the book
+    pub fn insert(&mut self, side: Side, price: f64, order_id: u64, size: f64) {
+        let book = match side {
+            Side::Buy => &mut self.bids,
+            Side::Sell => &mut self.asks,
+        };
+
+        book.entry(Price::from_f64(price))
+            .or_default()
+            .orders
+            .push_back((order_id, size));
+    }
+
+    /// Removes a resting order, returning whether it was found
+    pub fn cancel(&mut self, side: Side, price: f64, order_id: u64) -> bool {
+        let book = match side {
+            Side::Buy => &mut self.bids,
+            Side::Sell => &mut self.asks,
+        };
+        let price = Price::from_f64(price);
+
+        let Some(level) = book.get_mut(&price) else {
+            return false;
+        };
+
+        let before = level.orders.len();
+        level.orders.retain(|(id, _)| *id != order_id);
+        let found = level.orders.len() != before;
+
+        if level.orders.is_empty() {
+            book.remove(&price);
+        }
+
+        found
+    }
+
+    /// Applies an incoming trade print, crossing it against any of our
+    /// resting orders it would be marketable through. `size` is a single
+    /// budget shared across both sides: a print can only ever represent
+    /// that much liquidity once, even if it happens to be marketable
+    /// against resting orders on both the bid and the ask.
+    pub fn cross_trade(&mut self, price: f64, size: f64) -> Vec<Fill> {
+        self.last_trade_price = Some(price);
+
+        let mut remaining = size;
+        let mut fills = Self::sweep(
+            &mut self.bids,
+            Price::from_f64(price),
+            &mut remaining,
+            Side::Buy,
+            true,
+        );
+        fills.extend(Self::sweep(
+            &mut self.asks,
+            Price::from_f64(price),
+            &mut remaining,
+            Side::Sell,
+            false,
+        ));
+
+        fills
+    }
+
+    /// Fills resting orders on one side of the book that the incoming
+    /// trade at `trade_price` is marketable through: bids priced at or
+    /// above it, asks priced at or below it. Bids are swept highest
+    /// price first and asks lowest price first, so the most aggressive
+    /// resting orders fill before less aggressive ones at the same
+    /// trade print (price-time priority).
+    fn sweep(
+        book: &mut BTreeMap<Price, Level>,
+        trade_price: Price,
+        remaining: &mut f64,
+        side: Side,
+        is_bid: bool,
+    ) -> Vec<Fill> {
+        let mut fills = Vec::new();
+        let marketable: Vec<Price> = if is_bid {
+            book.range(trade_price..).rev().map(|(p, _)| *p).collect()
+        } else {
+            book.range(..=trade_price).map(|(p, _)| *p).collect()
+        };
+
+        for price in marketable {
+            if *remaining <= 0.0 {
+                break;
+            }
+
+            let Some(level) = book.get_mut(&price) else {
+                continue;
+            };
+
+            while *remaining > 0.0 {
+                let Some((order_id, size)) = level.orders.front().copied() else {
+                    break;
+                };
+
+                let fill_size = size.min(*remaining);
+                *remaining -= fill_size;
+
+                fills.push(Fill {
+                    order_id,
+                    side,
+                    price: price.as_f64(),
+                    size: fill_size,
+                });
+
+                if fill_size < size {
+                    level.orders[0].1 -= fill_size;
+                } else {
+                    level.orders.pop_front();
+                }
+            }
+
+            if level.orders.is_empty() {
+                book.remove(&price);
+            }
+        }
+
+        fills
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn market_order_fills_at_last_trade_price() {
+        let mut book = OrderBook::new();
+
+        assert!(book
+            .submit(Order {
+                id: 1,
+                symbol: "BTC-PERP".to_string(),
+                side: Side::Buy,
+                kind: OrderKind::Market,
+                size: 1.0,
+            })
+            .is_empty());
+
+        book.cross_trade(100.0, 1.0);
+
+        let fills = book.submit(Order {
+            id: 2,
+            symbol: "BTC-PERP".to_string(),
+            side: Side::Buy,
+            kind: OrderKind::Market,
+            size: 1.0,
+        });
+
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].price, 100.0);
+    }
+
+    #[test]
+    fn cross_trade_fills_bids_highest_price_first() {
+        let mut book = OrderBook::new();
+
+        book.insert(Side::Buy, 99.0, 1, 1.0);
+        book.insert(Side::Buy, 101.0, 2, 1.0);
+
+        // Both resting bids are marketable against a 99 print, but the
+        // more aggressive 101 bid must fill before the 99 one.
+        let fills = book.cross_trade(99.0, 1.0);
+
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].order_id, 2);
+        assert_eq!(book.best_bid(), Some(99.0));
+    }
+
+    #[test]
+    fn cross_trade_fills_asks_lowest_price_first() {
+        let mut book = OrderBook::new();
+
+        book.insert(Side::Sell, 101.0, 1, 1.0);
+        book.insert(Side::Sell, 99.0, 2, 1.0);
+
+        let fills = book.cross_trade(101.0, 1.0);
+
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].order_id, 2);
+        assert_eq!(book.best_ask(), Some(101.0));
+    }
+
+    #[test]
+    fn cross_trade_partially_fills_and_keeps_remainder_resting() {
+        let mut book = OrderBook::new();
+
+        book.insert(Side::Buy, 100.0, 1, 2.0);
+
+        let fills = book.cross_trade(100.0, 0.5);
+
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].size, 0.5);
+        assert_eq!(book.best_bid(), Some(100.0));
+    }
+
+    #[test]
+    fn cross_trade_splits_a_single_print_across_both_sides_of_the_book() {
+        let mut book = OrderBook::new();
+
+        // A 100 print is marketable against both the resting bid (100.0)
+        // and the resting ask (99.0). The print only represents 1.0 of
+        // liquidity, so the two sides must share that budget rather than
+        // each independently filling 1.0.
+        book.insert(Side::Buy, 100.0, 1, 1.0);
+        book.insert(Side::Sell, 99.0, 2, 1.0);
+
+        let fills = book.cross_trade(100.0, 1.0);
+
+        let total_size: f64 = fills.iter().map(|f| f.size).sum();
+        assert_eq!(total_size, 1.0);
+    }
+
+    #[test]
+    fn cancel_removes_order_and_empty_level() {
+        let mut book = OrderBook::new();
+
+        book.insert(Side::Buy, 100.0, 1, 1.0);
+
+        assert!(book.cancel(Side::Buy, 100.0, 1));
+        assert!(!book.cancel(Side::Buy, 100.0, 1));
+        assert_eq!(book.best_bid(), None);
+    }
+}
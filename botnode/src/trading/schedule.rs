@@ -0,0 +1,193 @@
+//! Wall-clock scheduling for actions the trading loop can't derive from
+//! queue events alone, e.g. weekly position rollover/expiry
+
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Utc, Weekday};
+
+/// How a [`ScheduleEntry`] reschedules itself after firing
+#[derive(Clone, Copy, Debug)]
+pub enum Recurrence {
+    /// Fires every `weekday` at `time` (UTC)
+    Weekly { weekday: Weekday, time: NaiveTime },
+}
+
+impl Recurrence {
+    /// The next occurrence strictly after `after`
+    fn next_after(&self, after: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            Recurrence::Weekly { weekday, time } => {
+                let mut candidate = after.date_naive().and_time(*time).and_utc();
+
+                while candidate.weekday() != *weekday || candidate <= after {
+                    candidate += Duration::days(1);
+                }
+
+                candidate
+            }
+        }
+    }
+}
+
+/// Action fired when a [`ScheduleEntry`] comes due
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScheduledAction {
+    /// Close out every open position at its last mark price
+    FlattenPositions,
+    /// Marks the rollover into the next trading period; the trading loop
+    /// logs it and leaves open positions unchanged
+    RollPositions,
+}
+
+struct ScheduleEntry {
+    next_fire: DateTime<Utc>,
+    recurrence: Recurrence,
+    action: ScheduledAction,
+}
+
+/// A set of fixed wall-clock actions polled from the trading loop
+///
+/// The loop is a tight busy-poll, so [`Schedule::poll`] is built to be
+/// cheap: it compares `now` against a single cached soonest fire time
+/// and only walks the entry list once that's passed.
+pub struct Schedule {
+    entries: Vec<ScheduleEntry>,
+    soonest: Option<DateTime<Utc>>,
+}
+
+impl Schedule {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            soonest: None,
+        }
+    }
+
+    /// Adds an entry, scheduling its first fire relative to `now` so an
+    /// engine started mid-window doesn't fire immediately for a window
+    /// that's already passed
+    pub fn add(&mut self, recurrence: Recurrence, action: ScheduledAction, now: DateTime<Utc>) {
+        let next_fire = recurrence.next_after(now);
+
+        self.entries.push(ScheduleEntry {
+            next_fire,
+            recurrence,
+            action,
+        });
+        self.recompute_soonest();
+    }
+
+    fn recompute_soonest(&mut self) {
+        self.soonest = self.entries.iter().map(|e| e.next_fire).min();
+    }
+
+    /// Returns the actions that came due at or before `now`, advancing
+    /// each fired entry to its next occurrence
+    pub fn poll(&mut self, now: DateTime<Utc>) -> Vec<ScheduledAction> {
+        match self.soonest {
+            Some(soonest) if now >= soonest => {}
+            _ => return Vec::new(),
+        }
+
+        let mut fired = Vec::new();
+
+        for entry in self.entries.iter_mut() {
+            if now >= entry.next_fire {
+                fired.push(entry.action);
+                entry.next_fire = entry.recurrence.next_after(entry.next_fire);
+            }
+        }
+
+        self.recompute_soonest();
+
+        fired
+    }
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weekly_friday_9pm() -> Recurrence {
+        Recurrence::Weekly {
+            weekday: Weekday::Fri,
+            time: NaiveTime::from_hms_opt(21, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn next_after_earlier_in_the_week_fires_this_friday() {
+        // Monday the same week
+        let now = DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let next = weekly_friday_9pm().next_after(now);
+
+        assert_eq!(next.weekday(), Weekday::Fri);
+        assert_eq!(next.date_naive(), "2024-01-05".parse().unwrap());
+    }
+
+    #[test]
+    fn next_after_past_this_weeks_fire_time_wraps_to_next_friday() {
+        // Friday, just after 21:00 on fire day itself
+        let now = DateTime::parse_from_rfc3339("2024-01-05T21:00:01Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let next = weekly_friday_9pm().next_after(now);
+
+        assert_eq!(next.weekday(), Weekday::Fri);
+        assert_eq!(next.date_naive(), "2024-01-12".parse().unwrap());
+    }
+
+    #[test]
+    fn next_after_exactly_at_fire_time_is_not_reentrant() {
+        // `next_after` returns the *next* occurrence strictly after `after`,
+        // so calling it again with its own result as `now` must not return
+        // the same instant back.
+        let now = DateTime::parse_from_rfc3339("2024-01-05T21:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let next = weekly_friday_9pm().next_after(now);
+
+        assert!(next > now);
+        assert_eq!(next.date_naive(), "2024-01-12".parse().unwrap());
+    }
+
+    #[test]
+    fn poll_fires_and_reschedules_entries_that_came_due() {
+        let mut schedule = Schedule::new();
+        let start = DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        schedule.add(weekly_friday_9pm(), ScheduledAction::FlattenPositions, start);
+
+        // Before the fire time: nothing due yet
+        assert!(schedule.poll(start).is_empty());
+
+        let fire_time = DateTime::parse_from_rfc3339("2024-01-05T21:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let fired = schedule.poll(fire_time);
+        assert_eq!(fired, vec![ScheduledAction::FlattenPositions]);
+
+        // Immediately after firing, it shouldn't fire again until next week
+        assert!(schedule.poll(fire_time).is_empty());
+
+        let next_week = DateTime::parse_from_rfc3339("2024-01-12T21:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            schedule.poll(next_week),
+            vec![ScheduledAction::FlattenPositions]
+        );
+    }
+}